@@ -3,7 +3,7 @@ use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 use std::fs::File;
 use std::io::ErrorKind;
-use std::io::{self, BufRead, BufReader, Write};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::process;
 use std::str::FromStr;
 
@@ -12,27 +12,94 @@ struct Config {
     sample_size: Option<usize>,
     rate: Option<f64>,
     seed: Option<u64>,
-    filename: Option<String>,
+    filenames: Vec<String>,
     preserve_headers: Option<usize>,
+    null_mode: bool,
+    with_replacement: bool,
+    range: Option<(i64, i64)>,
 }
 
-/// Perform reservoir sampling on lines from an iterator
-fn reservoir_sample<I>(lines: I, k: usize, mut rng: StdRng) -> io::Result<Vec<String>>
+/// Iterator over NUL-delimited records from a `BufRead`, mirroring `find -print0`
+struct NulRecords<R> {
+    reader: R,
+}
+
+impl<R: BufRead> NulRecords<R> {
+    fn new(reader: R) -> Self {
+        NulRecords { reader }
+    }
+}
+
+impl<R: BufRead> Iterator for NulRecords<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = Vec::new();
+        match self.reader.read_until(0, &mut buf) {
+            Ok(0) => None,
+            Ok(_) => {
+                if buf.last() == Some(&0) {
+                    buf.pop();
+                }
+                Some(String::from_utf8(buf).map_err(|e| io::Error::new(ErrorKind::InvalidData, e)))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Draw a uniform random value from the open interval (0, 1), never 0
+fn random_open_unit(rng: &mut StdRng) -> f64 {
+    loop {
+        let x = rng.gen::<f64>();
+        if x > 0.0 {
+            // A 0.0 would feed ln() as -inf and poison the running weight below.
+            return x;
+        }
+    }
+}
+
+/// Perform reservoir sampling on lines from an iterator using Vitter's Algorithm L
+fn reservoir_sample<I>(mut lines: I, k: usize, mut rng: StdRng) -> io::Result<Vec<String>>
 where
     I: Iterator<Item = Result<String, io::Error>>,
 {
+    if k == 0 {
+        return Ok(Vec::new());
+    }
+
     let mut reservoir: Vec<String> = Vec::with_capacity(k);
 
-    for (total, line_result) in lines.enumerate() {
-        let line = line_result?;
-        if total < k {
-            reservoir.push(line);
-        } else {
-            let j = rng.gen_range(0..=total);
-            if j < k {
-                reservoir[j] = line;
+    for line_result in lines.by_ref().take(k) {
+        reservoir.push(line_result?);
+    }
+
+    if reservoir.len() < k {
+        // Fewer lines than the reservoir size; nothing left to skip over.
+        return Ok(reservoir);
+    }
+
+    let mut w = (random_open_unit(&mut rng).ln() / k as f64).exp();
+
+    'outer: loop {
+        let skip = (random_open_unit(&mut rng).ln() / (1.0 - w).ln()).floor() as u64 + 1;
+
+        let mut admitted = None;
+        for _ in 0..skip {
+            match lines.next() {
+                Some(Ok(line)) => admitted = Some(line),
+                Some(Err(e)) => return Err(e),
+                None => break 'outer,
             }
         }
+
+        let Some(line) = admitted else {
+            break;
+        };
+
+        let j = rng.gen_range(0..k);
+        reservoir[j] = line;
+        w *= (random_open_unit(&mut rng).ln() / k as f64).exp();
     }
 
     Ok(reservoir)
@@ -53,13 +120,63 @@ where
     Ok(sampled)
 }
 
+/// Draw k samples with replacement from the input, as with `shuf --repeat`
+fn sample_with_replacement<I>(lines: I, k: usize, mut rng: StdRng) -> io::Result<Vec<String>>
+where
+    I: Iterator<Item = Result<String, io::Error>>,
+{
+    let pool: Vec<String> = lines.collect::<Result<_, _>>()?;
+
+    if pool.is_empty() {
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "cannot sample with replacement from empty input",
+        ));
+    }
+
+    let mut sampled = Vec::with_capacity(k);
+    for _ in 0..k {
+        let idx = rng.gen_range(0..pool.len());
+        sampled.push(pool[idx].clone());
+    }
+
+    Ok(sampled)
+}
+
+/// Shuffle all input lines into a uniformly random permutation, as with `shuf` given no -n/-r
+fn shuffle_all<I>(lines: I, mut rng: StdRng) -> io::Result<Vec<String>>
+where
+    I: Iterator<Item = Result<String, io::Error>>,
+{
+    let mut items: Vec<String> = lines.collect::<Result<_, _>>()?;
+
+    for i in (1..items.len()).rev() {
+        let j = rng.gen_range(0..=i);
+        items.swap(i, j);
+    }
+
+    Ok(items)
+}
+
+/// Write a single record, terminated with NUL in `--null` mode and `\n` otherwise
+fn write_record<W: Write>(handle: &mut W, record: &str, null_mode: bool) -> io::Result<()> {
+    if null_mode {
+        write!(handle, "{}\0", record)
+    } else {
+        writeln!(handle, "{}", record)
+    }
+}
+
 /// Write sampled lines to stdout, handling broken pipes gracefully
-fn write_results(lines: Vec<String>) -> io::Result<()> {
+fn write_results(lines: Vec<String>, null_mode: bool) -> io::Result<()> {
     let stdout = io::stdout();
     let mut handle = stdout.lock();
 
     for line in lines {
-        match writeln!(handle, "{}", line) {
+        match write_record(&mut handle, &line, null_mode) {
             Ok(_) => (),
             Err(e) if e.kind() == ErrorKind::BrokenPipe => process::exit(0),
             Err(e) => return Err(e),
@@ -72,7 +189,10 @@ fn write_results(lines: Vec<String>) -> io::Result<()> {
 /// Parse command line arguments using clap
 fn parse_args() -> Config {
     let matches = Command::new("samp")
-        .about("Randomly sample lines from a file or stdin")
+        .about(
+            "Randomly sample lines from a file or stdin \
+             (shuffles all lines if neither -n nor -r is given)",
+        )
         .arg(
             Arg::new("sample_size")
                 .short('n')
@@ -120,13 +240,56 @@ fn parse_args() -> Config {
         .arg(
             Arg::new("file")
                 .value_name("FILE")
-                .help("Input file (reads from stdin if not provided)")
+                .help(
+                    "Input file(s), sampled as one concatenated stream (reads stdin if none given)",
+                )
+                .num_args(1..)
                 .index(1),
         )
+        .arg(
+            Arg::new("null")
+                .short('z')
+                .long("null")
+                .help("Records are NUL-terminated instead of newline-terminated")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("with_replacement")
+                .short('R')
+                .long("with-replacement")
+                .help("Sample with replacement, like `shuf --repeat` (requires -n)")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("rate"),
+        )
+        .arg(
+            Arg::new("range")
+                .short('i')
+                .long("range")
+                .value_name("LO-HI")
+                .help("Sample from the integer sequence LO..=HI instead of a file or stdin")
+                .conflicts_with("file")
+                .value_parser(|s: &str| {
+                    let (lo, hi) = s
+                        .split_once('-')
+                        .ok_or_else(|| String::from("Range must be in the form LO-HI"))?;
+                    let lo: i64 = lo
+                        .parse()
+                        .map_err(|_| String::from("Range bounds must be integers"))?;
+                    let hi: i64 = hi
+                        .parse()
+                        .map_err(|_| String::from("Range bounds must be integers"))?;
+                    if lo > hi {
+                        return Err(String::from("Range LO must be <= HI"));
+                    }
+                    Ok((lo, hi))
+                }),
+        )
         .after_help(
             "Example usage:
     samp -n 10 file.txt
-    samp -r 0.05 < file.txt",
+    samp -r 0.05 < file.txt
+    samp file.txt              # shuffle all lines
+    find . -print0 | samp -z -n 10",
         )
         .get_matches();
 
@@ -141,41 +304,66 @@ fn parse_args() -> Config {
         sample_size: matches.get_one::<usize>("sample_size").copied(),
         rate: matches.get_one::<f64>("rate").copied(),
         seed: matches.get_one::<u64>("seed").copied(),
-        filename: matches.get_one::<String>("file").cloned(),
+        filenames: matches
+            .get_many::<String>("file")
+            .map(|vals| vals.cloned().collect())
+            .unwrap_or_default(),
         preserve_headers,
+        null_mode: matches.get_flag("null"),
+        with_replacement: matches.get_flag("with_replacement"),
+        range: matches.get_one::<(i64, i64)>("range").copied(),
     }
 }
 
 fn main() -> io::Result<()> {
     let config = parse_args();
 
-    if config.sample_size.is_none() && config.rate.is_none() {
-        eprintln!("Error: Must specify either -n <NUM> or -r <RATE>");
+    if config.with_replacement && config.sample_size.is_none() {
+        eprintln!("Error: -R/--with-replacement requires -n <NUM>");
         process::exit(1);
     }
 
     // Set up the input source
-    let reader: Box<dyn BufRead> = match &config.filename {
-        Some(file) => {
-            let f = match File::open(file) {
-                Ok(file) => file,
-                Err(e) => {
-                    eprintln!("Error: cannot open input file: {}", e);
-                    process::exit(1);
+    let mut lines: Box<dyn Iterator<Item = io::Result<String>>> =
+        if let Some((lo, hi)) = config.range {
+            Box::new((lo..=hi).map(|n| Ok(n.to_string())))
+        } else {
+            let reader: Box<dyn BufRead> = if config.filenames.is_empty() {
+                Box::new(BufReader::new(io::stdin()))
+            } else {
+                let mut chained: Box<dyn Read> = Box::new(io::empty());
+                for path in &config.filenames {
+                    let f = match File::open(path) {
+                        Ok(f) => f,
+                        Err(e) => {
+                            eprintln!("Error: cannot open input file '{}': {}", path, e);
+                            process::exit(1);
+                        }
+                    };
+                    chained = Box::new(chained.chain(f));
                 }
+                Box::new(BufReader::new(chained))
             };
-            Box::new(BufReader::new(f))
-        }
-        None => Box::new(BufReader::new(io::stdin())),
-    };
 
-    let mut lines = reader.lines();
+            if config.null_mode {
+                Box::new(NulRecords::new(reader))
+            } else {
+                Box::new(reader.lines())
+            }
+        };
 
     // Output preserved headers
     if let Some(num_headers) = config.preserve_headers {
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
         for _ in 0..num_headers {
             match lines.next() {
-                Some(Ok(line)) => println!("{}", line),
+                Some(Ok(line)) => {
+                    if let Err(e) = write_record(&mut handle, &line, config.null_mode) {
+                        eprintln!("Error writing output: {}", e);
+                        process::exit(1);
+                    }
+                }
                 Some(Err(e)) => {
                     eprintln!("Error reading input: {}", e);
                     process::exit(1);
@@ -192,14 +380,24 @@ fn main() -> io::Result<()> {
 
     // Dispatch to appropriate sampling method
     let result = if let Some(k) = config.sample_size {
-        reservoir_sample(lines, k, rng)?
+        if config.with_replacement {
+            match sample_with_replacement(lines, k, rng) {
+                Ok(sampled) => sampled,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                }
+            }
+        } else {
+            reservoir_sample(lines, k, rng)?
+        }
     } else if let Some(p) = config.rate {
         probability_sample(lines, p, rng)?
     } else {
-        unreachable!() // We've already checked that one must be set
+        shuffle_all(lines, rng)?
     };
 
-    write_results(result)?;
+    write_results(result, config.null_mode)?;
 
     Ok(())
 }
@@ -276,6 +474,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reservoir_sample_k_zero() {
+        let input_data = "a\nb\nc\n";
+        let reader = Cursor::new(input_data);
+        let rng = StdRng::seed_from_u64(17);
+
+        let sample = reservoir_sample(reader.lines(), 0, rng).unwrap();
+
+        assert!(sample.is_empty());
+    }
+
     #[test]
     fn test_preserve_headers() {
         let input = "h1\nh2\na\nb\nc\nd\n";
@@ -611,4 +820,281 @@ mod tests {
             stderr
         );
     }
+
+    #[test]
+    fn test_null_delimited_mode_preserves_embedded_newlines() {
+        let input_data: &[u8] = b"multi\nline\0single\0";
+        let exe_path = find_executable();
+
+        let output = Command::new(&exe_path)
+            .arg("-n")
+            .arg("2")
+            .arg("-z")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                child.stdin.as_mut().unwrap().write_all(input_data)?;
+                child.wait_with_output()
+            })
+            .expect("Failed to run samp");
+
+        let records: Vec<&[u8]> = output
+            .stdout
+            .split(|&b| b == 0)
+            .filter(|r| !r.is_empty())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert!(records.contains(&&b"multi\nline"[..]));
+        assert!(records.contains(&&b"single"[..]));
+    }
+
+    #[test]
+    fn test_range_input_mode() {
+        let exe_path = find_executable();
+
+        let output = Command::new(&exe_path)
+            .arg("-i")
+            .arg("1-5")
+            .arg("-n")
+            .arg("3")
+            .arg("-s")
+            .arg("7")
+            .output()
+            .expect("Failed to execute process");
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        let result_lines: Vec<&str> = result.lines().collect();
+
+        assert_eq!(result_lines.len(), 3);
+        for line in &result_lines {
+            let n: i64 = line.parse().expect("expected an integer");
+            assert!((1..=5).contains(&n));
+        }
+    }
+
+    #[test]
+    fn test_range_input_mode_conflicts_with_file() {
+        let exe_path = find_executable();
+
+        let output = Command::new(&exe_path)
+            .arg("-i")
+            .arg("1-5")
+            .arg("somefile.txt")
+            .output()
+            .expect("Failed to execute process");
+
+        assert!(
+            !output.status.success(),
+            "Expected failure when -i is combined with a FILE argument"
+        );
+    }
+
+    #[test]
+    fn test_default_shuffle_mode() {
+        let input_data = "a\nb\nc\nd\ne\n";
+        let exe_path = find_executable();
+
+        let output = Command::new(&exe_path)
+            .arg("-s")
+            .arg("42")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                child
+                    .stdin
+                    .as_mut()
+                    .unwrap()
+                    .write_all(input_data.as_bytes())?;
+                child.wait_with_output()
+            })
+            .expect("Failed to run samp");
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        let mut result_lines: Vec<&str> = result.lines().collect();
+        let mut expected: Vec<&str> = input_data.lines().collect();
+
+        assert_eq!(result_lines.len(), expected.len());
+        result_lines.sort();
+        expected.sort();
+        assert_eq!(result_lines, expected);
+    }
+
+    #[test]
+    fn test_default_shuffle_mode_preserves_headers() {
+        let input = "HEADER\na\nb\nc\n";
+        let exe_path = find_executable();
+
+        let output = Command::new(&exe_path)
+            .arg("-p")
+            .arg("1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                child.stdin.as_mut().unwrap().write_all(input.as_bytes())?;
+                child.wait_with_output()
+            })
+            .expect("Failed to run samp");
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        let mut lines = result.lines();
+
+        assert_eq!(lines.next(), Some("HEADER"));
+
+        let mut rest: Vec<&str> = lines.collect();
+        rest.sort();
+        assert_eq!(rest, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_multiple_input_files() {
+        let exe_path = find_executable();
+
+        let mut file1 = NamedTempFile::new().expect("Failed to create temp file");
+        file1
+            .write_all(b"a\nb\n")
+            .expect("Failed to write to temp file");
+        let mut file2 = NamedTempFile::new().expect("Failed to create temp file");
+        file2
+            .write_all(b"c\nd\n")
+            .expect("Failed to write to temp file");
+
+        let output = Command::new(&exe_path)
+            .arg(file1.path())
+            .arg(file2.path())
+            .arg("-n")
+            .arg("4")
+            .output()
+            .expect("Failed to execute process");
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        let mut result_lines: Vec<&str> = result.lines().collect();
+        result_lines.sort();
+
+        assert_eq!(result_lines, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_multiple_input_files_preserve_headers_first_file_only() {
+        let exe_path = find_executable();
+
+        let mut file1 = NamedTempFile::new().expect("Failed to create temp file");
+        file1
+            .write_all(b"HEADER\na\n")
+            .expect("Failed to write to temp file");
+        let mut file2 = NamedTempFile::new().expect("Failed to create temp file");
+        file2
+            .write_all(b"b\n")
+            .expect("Failed to write to temp file");
+
+        let output = Command::new(&exe_path)
+            .arg(file1.path())
+            .arg(file2.path())
+            .arg("-n")
+            .arg("2")
+            .arg("-p")
+            .arg("1")
+            .output()
+            .expect("Failed to execute process");
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        let mut lines = result.lines();
+
+        assert_eq!(lines.next(), Some("HEADER"));
+
+        let mut rest: Vec<&str> = lines.collect();
+        rest.sort();
+        assert_eq!(rest, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_with_replacement_allows_k_greater_than_input_len() {
+        let input_data = "a\nb\nc\n";
+        let exe_path = find_executable();
+
+        let output = Command::new(&exe_path)
+            .arg("-n")
+            .arg("10")
+            .arg("-R")
+            .arg("-s")
+            .arg("17")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                child
+                    .stdin
+                    .as_mut()
+                    .unwrap()
+                    .write_all(input_data.as_bytes())?;
+                child.wait_with_output()
+            })
+            .expect("Failed to run samp");
+
+        let result = String::from_utf8_lossy(&output.stdout);
+        let result_lines: Vec<&str> = result.lines().collect();
+
+        assert_eq!(result_lines.len(), 10);
+        for line in result_lines {
+            assert!(input_data.contains(line));
+        }
+    }
+
+    #[test]
+    fn test_with_replacement_empty_input_error() {
+        let exe_path = find_executable();
+
+        let output = Command::new(&exe_path)
+            .arg("-n")
+            .arg("5")
+            .arg("-R")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|child| child.wait_with_output())
+            .expect("Failed to run samp");
+
+        assert!(
+            !output.status.success(),
+            "Expected failure for -R on empty input, got success"
+        );
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.starts_with("Error: "),
+            "Expected a clean 'Error: ...' message, got: {}",
+            stderr
+        );
+        assert!(
+            stderr.contains("cannot sample with replacement from empty input"),
+            "Unexpected stderr: {}",
+            stderr
+        );
+    }
+
+    #[test]
+    fn test_with_replacement_requires_sample_size() {
+        let exe_path = find_executable();
+
+        let output = Command::new(&exe_path)
+            .arg("-R")
+            .output()
+            .expect("Failed to execute process");
+
+        assert!(
+            !output.status.success(),
+            "Expected failure for -R without -n, got success"
+        );
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("-R/--with-replacement requires -n"),
+            "Unexpected stderr: {}",
+            stderr
+        );
+    }
 }